@@ -1,21 +1,20 @@
-use rgb::RGBA8;
 use gif::DisposalMethod;
 use imgref::*;
 use std::default::Default;
 
-enum SavedState {
-    Previous(Vec<RGBA8>),
-    Background,
+enum SavedState<PixelType> {
+    Previous(Vec<PixelType>),
+    Background(PixelType),
     Keep,
 }
 
-pub struct Disposal {
-    saved: SavedState,
+pub struct Disposal<PixelType> {
+    saved: SavedState<PixelType>,
     left: u16, top: u16,
     width: u16, height: u16,
 }
 
-impl Default for Disposal {
+impl<PixelType> Default for Disposal<PixelType> {
     fn default() -> Self {
         Disposal {
            saved: SavedState::Keep,
@@ -24,16 +23,16 @@ impl Default for Disposal {
    }
 }
 
-impl Disposal {
-    pub fn dispose(&self, mut pixels: ImgRefMut<'_, RGBA8>) {
+impl<PixelType: Copy + Default> Disposal<PixelType> {
+    pub fn dispose(&self, mut pixels: ImgRefMut<'_, PixelType>) {
         if self.width == 0 || self.height == 0 {
             return;
         }
 
         let mut dest = pixels.sub_image_mut(self.left.into(), self.top.into(), self.width.into(), self.height.into());
         match &self.saved {
-            SavedState::Background => {
-                let bg = RGBA8::default();
+            SavedState::Background(bg) => {
+                let bg = *bg;
                 for px in dest.pixels_mut() { *px = bg; }
             },
             SavedState::Previous(saved) => {
@@ -43,11 +42,13 @@ impl Disposal {
         }
     }
 
-    pub fn new(method: gif::DisposalMethod, left: u16, top: u16, width: u16, height: u16, pixels: ImgRef<'_, RGBA8>) -> Self {
+    /// `background` is the resolved fill color to use for `DisposalMethod::Background`,
+    /// or `None` to fill with transparency (`PixelType::default()`).
+    pub fn new(method: gif::DisposalMethod, left: u16, top: u16, width: u16, height: u16, pixels: ImgRef<'_, PixelType>, background: Option<PixelType>) -> Self {
         Disposal {
             saved: match method {
                 DisposalMethod::Previous => SavedState::Previous(pixels.sub_image(left.into(), top.into(), width.into(), height.into()).pixels().collect()),
-                DisposalMethod::Background => SavedState::Background,
+                DisposalMethod::Background => SavedState::Background(background.unwrap_or_default()),
                 _ => SavedState::Keep,
             },
             left, top, width, height,
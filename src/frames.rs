@@ -0,0 +1,98 @@
+use crate::{Error, Screen};
+use imgref::ImgRef;
+use rgb::RGBA8;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// A single animation frame, fully composed onto the virtual screen. See [`Frames`].
+pub struct ComposedFrame<'screen> {
+    pixels: ImgRef<'screen, RGBA8>,
+    /// How long this frame should be displayed for.
+    pub delay: Duration,
+    /// Offset of the frame's own (not the canvas') rect that was just drawn.
+    pub left: u16,
+    /// Offset of the frame's own (not the canvas') rect that was just drawn.
+    pub top: u16,
+    /// Size of the frame's own rect that was just drawn.
+    pub width: u16,
+    /// Size of the frame's own rect that was just drawn.
+    pub height: u16,
+}
+
+impl<'screen> ComposedFrame<'screen> {
+    /// The whole canvas (GIF size), composed so far. This is the frame to display.
+    #[inline(always)]
+    #[must_use]
+    pub fn pixels(&self) -> ImgRef<'_, RGBA8> {
+        self.pixels
+    }
+}
+
+/// High-level animation iterator. See [`Screen::into_frames`].
+///
+/// Unlike a regular `Iterator`, frames borrow the screen they're composed onto,
+/// so call [`Frames::next`] in a `while let` loop rather than a `for` loop.
+pub struct Frames<T: io::Read> {
+    screen: Screen<RGBA8>,
+    decoder: gif::Decoder<T>,
+}
+
+impl<T: io::Read> Frames<T> {
+    pub(crate) fn new(screen: Screen<RGBA8>, decoder: gif::Decoder<T>) -> Self {
+        Self { screen, decoder }
+    }
+
+    /// The animation's loop count, parsed from the NETSCAPE/ANIMEXTS application extension.
+    #[must_use]
+    pub fn repeat(&self) -> gif::Repeat {
+        self.decoder.repeat()
+    }
+
+    /// Advance to the next frame, disposing of the previous one and blitting the new one
+    /// onto the virtual screen. Returns `None` once the GIF has no more frames.
+    // Frames intentionally isn't a real `Iterator`: yielded frames borrow `self`, which
+    // `Iterator::next`'s signature can't express.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<ComposedFrame<'_>>, FramesError> {
+        let Some(frame) = self.decoder.read_next_frame().map_err(FramesError::Decode)? else {
+            return Ok(None);
+        };
+        let delay = Duration::from_millis(u64::from(frame.delay) * 10);
+        let (left, top, width, height) = (frame.left, frame.top, frame.width, frame.height);
+        self.screen.blit_frame(frame).map_err(FramesError::Screen)?;
+        Ok(Some(ComposedFrame {
+            pixels: self.screen.pixels_rgba(),
+            delay, left, top, width, height,
+        }))
+    }
+}
+
+/// Error advancing a [`Frames`] iterator.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FramesError {
+    /// See [`Error`].
+    Screen(Error),
+    /// The underlying GIF stream could not be decoded.
+    Decode(gif::DecodingError),
+}
+
+impl fmt::Display for FramesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Screen(e) => e.fmt(f),
+            Self::Decode(e) => e.fmt(f),
+        }
+    }
+}
+
+impl StdError for FramesError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Screen(e) => Some(e),
+            Self::Decode(e) => Some(e),
+        }
+    }
+}
@@ -23,10 +23,14 @@
 //! ```
 
 mod disposal;
+mod frames;
 mod screen;
 
+pub use crate::frames::{ComposedFrame, Frames, FramesError};
+pub use crate::screen::FrameDiff;
 pub use crate::screen::Screen;
 pub use crate::screen::TempDisposedStateScreen;
+pub use gif::Repeat;
 pub use rgb::{RGB8, RGBA8};
 pub use imgref::ImgRef;
 
@@ -38,11 +42,16 @@ use std::fmt;
 pub enum Error {
     /// GIF must have either a global palette set, or per-frame palette set. If there is none, it's not possible to render.
     NoPalette,
+    /// [`TempDisposedStateScreen::diff_against`]'s `target` must have the same dimensions as the canvas.
+    DimensionMismatch,
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("No palette")
+        f.write_str(match self {
+            Self::NoPalette => "No palette",
+            Self::DimensionMismatch => "Mismatched image dimensions",
+        })
     }
 }
 
@@ -1,52 +1,147 @@
 use super::Error;
 use crate::disposal::Disposal;
+use crate::frames::Frames;
 use imgref::*;
 use rgb::*;
 use std::io;
 
 /// Combined GIF frames forming a "virtual screen". See [Screen::new_decoder].
 ///
-/// Pixel type can be `RGB8` or `RGBA8`. The size is overall GIF size (grater or equal individual frame sizes).
-pub struct Screen {
+/// `PixelType` is `RGBA8` by default. Use `Screen<RGB8>` if you don't care about
+/// transparency and want to compose frames directly onto an opaque canvas
+/// (transparent source pixels are simply left unchanged).
+///
+/// The size is overall GIF size (grater or equal individual frame sizes).
+pub struct Screen<PixelType = RGBA8> {
     /// Result of combining all frames so far. It's in RGB/RGBA.
-    internal_pixels: ImgVec<RGBA8>,
+    internal_pixels: ImgVec<PixelType>,
 
     global_pal: Option<[RGB8; 256]>,
-    next_disposal: Disposal,
+    background: Option<RGB8>,
+    /// Raw palette index backing `background`, kept separately so the index plane can be
+    /// filled with the real index rather than always falling back to 0.
+    background_index: Option<u8>,
+    next_disposal: Disposal<PixelType>,
+
+    /// Raw palette indices, kept in lockstep with `internal_pixels` once enabled. See
+    /// [`Screen::enable_indexed_pixels`].
+    index_plane: Option<ImgVec<u8>>,
+    next_index_disposal: Disposal<u8>,
+    /// Set once a blit wrote indices under a frame's own local palette, which makes
+    /// `index_plane`'s indices meaningless against `global_pal`. See [`Screen::indexed_pixels`].
+    index_plane_has_local_palette: bool,
 }
 
-impl Screen {
+impl Screen<RGBA8> {
     /// Create an new `Screen`
     ///
     /// Make sure Reader is set to use `Indexed` color.
     /// `options.set_color_output(gif::ColorOutput::Indexed);`
     #[must_use]
     pub fn new_decoder<T: io::Read>(reader: &gif::Decoder<T>) -> Self {
+        Self::from_decoder(reader)
+    }
+
+    /// Access the currently rendered pixels in RGBA.
+    ///
+    /// Shorthand for [`pixels()`](Screen::pixels) that pins the pixel type to `RGBA8`.
+    #[inline(always)]
+    pub fn pixels_rgba(&mut self) -> ImgRef<'_, RGBA8> {
+        self.pixels()
+    }
+
+    /// A high-level iterator that drives `decoder` for you, disposing of and blitting each
+    /// frame in turn, and yielding the fully-composed frame along with its delay.
+    ///
+    /// `self` should have been created from `decoder` via [`Screen::new_decoder`].
+    #[must_use]
+    pub fn into_frames<T: io::Read>(self, decoder: gif::Decoder<T>) -> Frames<T> {
+        Frames::new(self, decoder)
+    }
+}
+
+impl<PixelType: From<RGB8> + Copy + Default> Screen<PixelType> {
+    /// Create a new `Screen` with a custom pixel type, e.g. `Screen::<RGB8>::from_decoder(&decoder)`.
+    ///
+    /// Make sure Reader is set to use `Indexed` color.
+    /// `options.set_color_output(gif::ColorOutput::Indexed);`
+    #[must_use]
+    pub fn from_decoder<T: io::Read>(reader: &gif::Decoder<T>) -> Self {
         let w = reader.width();
         let h = reader.height();
         let pal = reader.global_palette().map(|pal| pal.as_rgb());
-        Self::new(w.into(), h.into(), pal)
+        // GIF background color index is always <256; a GIF that somehow claims otherwise
+        // just ends up with no resolved background rather than a panic.
+        let bg_color_index = reader.bg_color().and_then(|i| u8::try_from(i).ok());
+        Self::new(w.into(), h.into(), pal, bg_color_index)
     }
 
-    /// Manual setup of the canvas. You probably should use `new_decoder` instead.
+    /// Manual setup of the canvas. You probably should use `new_decoder`/`from_decoder` instead.
+    ///
+    /// `bg_color_index` is the logical screen descriptor's background color index (e.g.
+    /// `decoder.bg_color()`), resolved against `global_pal`. It's used to fill frames that
+    /// dispose to the background instead of to transparency; see [`Screen::set_background`].
     ///
     /// Use `rgb` crate's `as_rgb()` if you have palette as `&[u8]`.
     #[inline]
     #[must_use]
-    pub fn new(width: usize, height: usize, global_pal: Option<&[RGB8]>) -> Self {
+    pub fn new(width: usize, height: usize, global_pal: Option<&[RGB8]>, bg_color_index: Option<u8>) -> Self {
+        let background = bg_color_index.and_then(|i| global_pal.and_then(|g| g.get(usize::from(i)).copied()));
         Screen {
-            internal_pixels: Img::new(vec![RGBA8::default(); width * height], width, height),
+            internal_pixels: Img::new(vec![PixelType::default(); width * height], width, height),
             global_pal: global_pal.map(|g| std::array::from_fn(move |i| g.get(i).copied().unwrap_or_default())),
+            background,
+            background_index: bg_color_index,
             next_disposal: Disposal::default(),
+            index_plane: None,
+            next_index_disposal: Disposal::default(),
+            index_plane_has_local_palette: false,
+        }
+    }
+
+    /// Override the color used to fill frames that dispose to the background
+    /// (`DisposalMethod::Background`) whenever no transparent index is in effect.
+    ///
+    /// Use this if you want an explicit canvas fill regardless of what the GIF's
+    /// logical screen descriptor declares. Note this doesn't have a corresponding palette
+    /// index, so the [index plane](Screen::indexed_pixels), if enabled, keeps using whatever
+    /// background index the GIF itself declared (or none).
+    pub fn set_background(&mut self, color: RGB8) {
+        self.background = Some(color);
+    }
+
+    /// Start tracking the raw palette index of every pixel alongside the composed colors, for
+    /// callers re-encoding the GIF (e.g. requantizing only changed regions against an existing
+    /// palette). See [`Screen::indexed_pixels`].
+    pub fn enable_indexed_pixels(&mut self) {
+        let (width, height) = (self.internal_pixels.width(), self.internal_pixels.height());
+        self.index_plane.get_or_insert_with(|| Img::new(vec![0u8; width * height], width, height));
+    }
+
+    /// The raw palette indices tracked since [`Screen::enable_indexed_pixels`] was called,
+    /// together with the global palette they're resolved against.
+    ///
+    /// Transparent pixels are left untouched rather than zeroed, so disposal keeps working on
+    /// indices the same way it does on colors. Returns `None` if index tracking was never
+    /// enabled, if the GIF has no global palette, or if any blitted frame carried its own local
+    /// palette: indices written under a local palette don't resolve to the right colors against
+    /// the global palette returned here, so this API refuses to hand back silently-wrong data.
+    #[must_use]
+    pub fn indexed_pixels(&self) -> Option<(ImgRef<'_, u8>, &[RGB8; 256])> {
+        if self.index_plane_has_local_palette {
+            return None;
         }
+        let plane = self.index_plane.as_ref()?;
+        let pal = self.global_pal.as_ref()?;
+        Some((plane.as_ref(), pal))
     }
 
     /// Advance the screen by one frame.
     ///
-    /// Use `pixels_rgba()` to get pixels afterwards
+    /// Use `pixels()` to get pixels afterwards
     pub fn blit_frame(&mut self, frame: &gif::Frame<'_>) -> Result<(), Error> {
         let local_pal = frame.palette.as_deref().map(|p| p.as_rgb());
-        self.blit(local_pal.map(|p| &p[..]), frame.dispose,
+        self.blit(local_pal, frame.dispose,
             frame.left, frame.top,
             ImgRef::new(&frame.buffer, frame.width.into(), frame.height.into()), frame.transparent)
     }
@@ -59,7 +154,14 @@ impl Screen {
     }
 
     fn blit_without_dispose(&mut self, local_pal: Option<&[RGB8]>, method: gif::DisposalMethod, left: u16, top: u16, buffer: ImgRef<'_, u8>, transparent: Option<u8>) -> Result<(), Error> {
-        self.next_disposal = Disposal::new(method, left, top, buffer.width() as u16, buffer.height() as u16, self.internal_pixels.as_ref());
+        // A transparent index in effect means this frame relies on alpha, so disposing to
+        // "background" should reveal transparency rather than an opaque background color.
+        let background_fill = if transparent.is_some() { None } else { self.background.map(PixelType::from) };
+        self.next_disposal = Disposal::new(method, left, top, buffer.width() as u16, buffer.height() as u16, self.internal_pixels.as_ref(), background_fill);
+        if let Some(plane) = self.index_plane.as_ref() {
+            let index_background_fill = if transparent.is_some() { None } else { self.background_index };
+            self.next_index_disposal = Disposal::new(method, left, top, buffer.width() as u16, buffer.height() as u16, plane.as_ref(), index_background_fill);
+        }
 
         let pal_slice = local_pal.or(self.global_pal.as_ref().map(|p| &p[..])).ok_or(Error::NoPalette)?;
         let pal: [_; 256] = std::array::from_fn(|i| {
@@ -70,23 +172,29 @@ impl Screen {
             if Some(src) == transparent {
                 continue;
             }
-            *dst = pal[src as usize].alpha(255);
+            *dst = PixelType::from(pal[src as usize]);
+        }
+
+        if let Some(plane) = self.index_plane.as_mut() {
+            if local_pal.is_some() {
+                self.index_plane_has_local_palette = true;
+            }
+            for (dst, src) in plane.sub_image_mut(left.into(), top.into(), buffer.width(), buffer.height()).pixels_mut().zip(buffer.pixels()) {
+                if Some(src) == transparent {
+                    continue;
+                }
+                *dst = src;
+            }
         }
         Ok(())
     }
 
     /// Access the currently rendered pixels
     #[inline(always)]
-    pub fn pixels_rgba(&mut self) -> ImgRef<'_, RGBA8> {
+    pub fn pixels(&mut self) -> ImgRef<'_, PixelType> {
         self.internal_pixels.as_ref()
     }
 
-    /// Use [`pixels_rgba`]
-    #[deprecated(note = "use pixels_rgba() instead. This method will return a different type in the next version")]
-    pub fn pixels(&mut self) -> ImgRef<'_, RGBA8> {
-        self.pixels_rgba()
-    }
-
     /// Advanced usage. You do not need to call this. It exposes an incompletely-drawn screen.
     ///
     /// Call to this method must always be followed by `.then_blit()` to fix the incomplete state.
@@ -100,15 +208,18 @@ impl Screen {
     /// ```rust
     /// # fn example(buffer: imgref::ImgRef<u8>) -> Result<(), gif_dispose::Error> {
     /// use gif_dispose::*;
-    /// let mut screen = Screen::new(320, 200, None);
+    /// let mut screen = Screen::<RGBA8>::new(320, 200, None, None);
     /// let mut tmp_screen = screen.dispose_only();
     /// let incomplete_pixels = tmp_screen.pixels();
     /// tmp_screen.then_blit(None, gif::DisposalMethod::Keep, 0, 0, buffer, None)?;
     /// # Ok(()) }
     /// ```
     #[inline]
-    pub fn dispose_only(&mut self) -> TempDisposedStateScreen<'_> {
+    pub fn dispose_only(&mut self) -> TempDisposedStateScreen<'_, PixelType> {
         self.next_disposal.dispose(self.internal_pixels.as_mut());
+        if let Some(plane) = self.index_plane.as_mut() {
+            self.next_index_disposal.dispose(plane.as_mut());
+        }
         TempDisposedStateScreen(self)
     }
 }
@@ -116,15 +227,15 @@ impl Screen {
 
 /// Screen that has a temporary state between frames
 #[must_use]
-pub struct TempDisposedStateScreen<'screen>(&'screen mut Screen);
+pub struct TempDisposedStateScreen<'screen, PixelType = RGBA8>(&'screen mut Screen<PixelType>);
 
 /// Extends borrow to the end of scope, reminding to use `then_blit`
-impl Drop for TempDisposedStateScreen<'_> {
+impl<PixelType> Drop for TempDisposedStateScreen<'_, PixelType> {
     fn drop(&mut self) {
     }
 }
 
-impl<'s, > TempDisposedStateScreen<'s> {
+impl<'s, PixelType: From<RGB8> + Copy + Default> TempDisposedStateScreen<'s, PixelType> {
     #[inline(always)]
     pub fn then_blit(self, local_pal: Option<&[RGB8]>, method: gif::DisposalMethod, left: u16, top: u16, buffer: ImgRef<'_, u8>, transparent: Option<u8>) -> Result<(), Error> {
         self.0.blit_without_dispose(local_pal, method, left, top, buffer, transparent)
@@ -132,14 +243,182 @@ impl<'s, > TempDisposedStateScreen<'s> {
 
     /// Access pixels in the in-between state
     #[inline(always)]
-    pub fn pixels_rgba(&mut self) -> ImgRef<'_, RGBA8> {
+    pub fn pixels(&mut self) -> ImgRef<'_, PixelType> {
         self.0.internal_pixels.as_ref()
     }
+}
+
+impl<'s> TempDisposedStateScreen<'s, RGBA8> {
+    /// Access pixels in the in-between state, in RGBA.
+    #[inline(always)]
+    pub fn pixels_rgba(&mut self) -> ImgRef<'_, RGBA8> {
+        self.pixels()
+    }
+}
+
+impl<'s, PixelType: PartialEq + Copy + Default> TempDisposedStateScreen<'s, PixelType> {
+    /// Compare the in-between (post-dispose) canvas against the frame an encoder wants to
+    /// write next, to find the minimal difference worth encoding.
+    ///
+    /// Returns the tight bounding rect of changed pixels, a mask of which pixels inside that
+    /// rect happen to already match (so the encoder can emit them as the transparent index),
+    /// and a suggested `DisposalMethod` for the `target` frame (see [`FrameDiff::method`]).
+    ///
+    /// An empty rect (`width == 0 || height == 0`) means `target` is identical to the current
+    /// canvas, so the encoder can drop the frame entirely.
+    ///
+    /// `target` must have the same dimensions as the canvas (the whole GIF, not one frame),
+    /// or this returns `Error::DimensionMismatch`.
+    pub fn diff_against(&self, target: ImgRef<'_, PixelType>) -> Result<FrameDiff, Error> {
+        // Not `self.pixels()`: that's only defined for `PixelType: From<RGB8>`, which this
+        // method doesn't need.
+        let canvas = self.0.internal_pixels.as_ref();
+        if canvas.width() != target.width() || canvas.height() != target.height() {
+            return Err(Error::DimensionMismatch);
+        }
+        let width = canvas.width();
+
+        let (mut min_x, mut min_y) = (canvas.width(), canvas.height());
+        let (mut max_x, mut max_y) = (0, 0);
+        for (i, (c, t)) in canvas.pixels().zip(target.pixels()).enumerate() {
+            if c != t {
+                let (x, y) = (i % width, i / width);
+                min_x = min_x.min(x); max_x = max_x.max(x);
+                min_y = min_y.min(y); max_y = max_y.max(y);
+            }
+        }
+        if min_x > max_x || min_y > max_y {
+            return Ok(FrameDiff {
+                left: 0, top: 0, width: 0, height: 0,
+                unchanged: Vec::new(),
+                method: gif::DisposalMethod::Keep,
+            });
+        }
+
+        let rect_width = max_x - min_x + 1;
+        let rect_height = max_y - min_y + 1;
+        let canvas_rect = canvas.sub_image(min_x, min_y, rect_width, rect_height);
+        let target_rect = target.sub_image(min_x, min_y, rect_width, rect_height);
+
+        let mut unchanged = Vec::with_capacity(rect_width * rect_height);
+        let mut needs_clear = false;
+        let mut only_adds = true;
+        for (c, t) in canvas_rect.pixels().zip(target_rect.pixels()) {
+            unchanged.push(c == t);
+            if c != t {
+                if t == PixelType::default() { needs_clear = true; }
+                if c != PixelType::default() { only_adds = false; }
+            }
+        }
+
+        let method = if needs_clear {
+            gif::DisposalMethod::Background
+        } else if only_adds {
+            gif::DisposalMethod::Keep
+        } else {
+            gif::DisposalMethod::Previous
+        };
+
+        Ok(FrameDiff {
+            left: min_x as u16, top: min_y as u16,
+            width: rect_width as u16, height: rect_height as u16,
+            unchanged,
+            method,
+        })
+    }
+}
+
+/// Result of [`TempDisposedStateScreen::diff_against`].
+#[derive(Debug, Clone)]
+pub struct FrameDiff {
+    /// Bounding rect of changed pixels, relative to the canvas.
+    pub left: u16,
+    pub top: u16,
+    /// An empty rect (`width == 0 || height == 0`) means the frames are identical.
+    pub width: u16,
+    pub height: u16,
+    /// Row-major, `width * height` flags: `true` where the target pixel already matches the
+    /// canvas, so it can be emitted as the transparent index instead of a real color.
+    pub unchanged: Vec<bool>,
+    /// Suggested disposal method for the frame being diffed against: `Background` when any
+    /// changed pixel becomes `PixelType::default()` (cleared), otherwise `Keep` when every
+    /// changed pixel was `PixelType::default()` on the canvas (it only adds pixels), otherwise
+    /// `Previous`.
+    pub method: gif::DisposalMethod,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexed_pixels_returns_none_after_local_palette_blit() {
+        let global_pal = [RGB8 { r: 0, g: 0, b: 0 }, RGB8 { r: 255, g: 255, b: 255 }];
+        let mut screen = Screen::<RGBA8>::new(2, 1, Some(&global_pal), None);
+        screen.enable_indexed_pixels();
+
+        let local_pal = [RGB8 { r: 255, g: 0, b: 0 }, RGB8 { r: 0, g: 255, b: 0 }];
+        screen.blit(Some(&local_pal), gif::DisposalMethod::Keep, 0, 0, ImgRef::new(&[0u8, 1u8], 2, 1), None).unwrap();
+
+        // The canvas itself is resolved through the local palette correctly...
+        let pixels: Vec<_> = screen.pixels().pixels().collect();
+        assert_eq!(pixels, [RGBA8 { r: 255, g: 0, b: 0, a: 255 }, RGBA8 { r: 0, g: 255, b: 0, a: 255 }]);
+
+        // ...but the raw indices can no longer be resolved against the global palette, so this
+        // must refuse to hand back silently-wrong data rather than resolve red/green as black/white.
+        assert!(screen.indexed_pixels().is_none());
+    }
+
+    #[test]
+    fn background_disposal_restores_real_color_and_index() {
+        let global_pal = [RGB8 { r: 0, g: 0, b: 0 }, RGB8 { r: 255, g: 255, b: 255 }];
+        let mut screen = Screen::<RGBA8>::new(2, 1, Some(&global_pal), Some(1));
+        screen.enable_indexed_pixels();
+
+        // Frame 1 fills the whole canvas black and disposes to background afterwards.
+        screen.blit(None, gif::DisposalMethod::Background, 0, 0, ImgRef::new(&[0u8, 0u8], 2, 1), None).unwrap();
+        // Frame 2 only touches the first pixel; disposing frame 1 should reveal the real
+        // background (white, index 1) in the second pixel, not black or index 0.
+        screen.blit(None, gif::DisposalMethod::Keep, 0, 0, ImgRef::new(&[0u8], 1, 1), None).unwrap();
+
+        let pixels: Vec<_> = screen.pixels().pixels().collect();
+        assert_eq!(pixels, [RGBA8 { r: 0, g: 0, b: 0, a: 255 }, RGBA8 { r: 255, g: 255, b: 255, a: 255 }]);
+
+        let (indices, pal) = screen.indexed_pixels().unwrap();
+        assert_eq!(indices.pixels().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(pal[..2], global_pal);
+    }
+
+    #[test]
+    fn diff_against_rejects_mismatched_dimensions() {
+        let mut screen = Screen::<RGBA8>::new(2, 1, None, None);
+        let tmp = screen.dispose_only();
+        let target = [RGBA8::default(); 3];
+        assert!(matches!(tmp.diff_against(ImgRef::new(&target, 3, 1)), Err(Error::DimensionMismatch)));
+    }
+
+    #[test]
+    fn diff_against_keep_when_only_adding_pixels() {
+        let mut screen = Screen::<RGBA8>::new(2, 1, None, None);
+        let tmp = screen.dispose_only();
+        let target = [RGBA8::default(), RGBA8 { r: 10, g: 20, b: 30, a: 255 }];
+        let diff = tmp.diff_against(ImgRef::new(&target, 2, 1)).unwrap();
+        assert_eq!((diff.left, diff.top, diff.width, diff.height), (1, 0, 1, 1));
+        assert_eq!(diff.unchanged, [false]);
+        assert_eq!(diff.method, gif::DisposalMethod::Keep);
+    }
 
+    #[test]
+    fn diff_against_background_when_clearing_pixels() {
+        let global_pal = [RGB8 { r: 100, g: 100, b: 100 }];
+        let mut screen = Screen::<RGBA8>::new(2, 1, Some(&global_pal), None);
+        screen.blit(None, gif::DisposalMethod::Keep, 0, 0, ImgRef::new(&[0u8, 0u8], 2, 1), None).unwrap();
 
-    /// Use [`pixels_rgba`]
-    #[deprecated(note = "use pixels_rgba() instead. This method will return a different type in the next version")]
-    pub fn pixels(&mut self) -> ImgRef<'_, RGBA8> {
-        self.pixels_rgba()
+        let tmp = screen.dispose_only();
+        let target = [RGBA8::default(), RGBA8 { r: 100, g: 100, b: 100, a: 255 }];
+        let diff = tmp.diff_against(ImgRef::new(&target, 2, 1)).unwrap();
+        assert_eq!((diff.left, diff.top, diff.width, diff.height), (0, 0, 1, 1));
+        assert_eq!(diff.unchanged, [false]);
+        assert_eq!(diff.method, gif::DisposalMethod::Background);
     }
 }